@@ -0,0 +1,46 @@
+//! Benchmarks for the dead-state memoization in
+//! `calc_postfix_sequences_first`, comparing it against the pre-memoization
+//! implementation on hard (near-miss target) and no-solution inputs where
+//! redundant re-exploration is most costly.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use countdown_numbers_solver::{
+    compute::{calc_postfix_sequences_first, calc_postfix_sequences_first_unmemoized},
+    util::Op,
+};
+
+const STANDARD_OPS: [Op; 4] = [Op::Add, Op::Sub, Op::Mul, Op::Div];
+
+fn bench_no_solution(c: &mut Criterion) {
+    // no combination of these numbers can reach this target
+    let numbers = [1, 2, 3, 4, 5, 6];
+    let target = 999;
+
+    let mut group = c.benchmark_group("first_solution/no_solution");
+    group.bench_function("memoized", |b| {
+        b.iter(|| calc_postfix_sequences_first(&numbers, target, false, &STANDARD_OPS))
+    });
+    group.bench_function("unmemoized", |b| {
+        b.iter(|| calc_postfix_sequences_first_unmemoized(&numbers, target, false, &STANDARD_OPS))
+    });
+    group.finish();
+}
+
+fn bench_hard_target(c: &mut Criterion) {
+    // a well-known "hard" Countdown target for this number set
+    let numbers = [25, 50, 75, 100, 3, 6];
+    let target = 952;
+
+    let mut group = c.benchmark_group("first_solution/hard_target");
+    group.bench_function("memoized", |b| {
+        b.iter(|| calc_postfix_sequences_first(&numbers, target, false, &STANDARD_OPS))
+    });
+    group.bench_function("unmemoized", |b| {
+        b.iter(|| calc_postfix_sequences_first_unmemoized(&numbers, target, false, &STANDARD_OPS))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_no_solution, bench_hard_target);
+criterion_main!(benches);
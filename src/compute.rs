@@ -1,7 +1,5 @@
 use std::collections::HashSet;
 
-use strum::IntoEnumIterator;
-
 use crate::util::{Op, Token};
 
 pub type PostfixSequence = Vec<Token>;
@@ -9,7 +7,7 @@ pub type PostfixSequence = Vec<Token>;
 /// Try to apply a token on the postfix stack.
 /// This will either push a number or apply an operation.
 /// Returns the updated stack unless operation is illegal.
-fn try_apply_legal(mut stack: Vec<u32>, token: Token) -> Option<Vec<u32>> {
+pub(crate) fn try_apply_legal(mut stack: Vec<u32>, token: Token) -> Option<Vec<u32>> {
     match token {
         Token::Num(n) => {
             stack.push(n);
@@ -20,10 +18,13 @@ fn try_apply_legal(mut stack: Vec<u32>, token: Token) -> Option<Vec<u32>> {
             let operand1 = stack.pop().unwrap();
             let operand0 = stack.pop().unwrap(); // at least 2 in stack
             match op {
-                Op::Add => {
-                    stack.push(operand0 + operand1);
-                    Some(stack)
-                }
+                Op::Add => match operand0.checked_add(operand1) {
+                    Some(result) => {
+                        stack.push(result);
+                        Some(stack)
+                    }
+                    None => None,
+                },
                 Op::Sub => {
                     if operand0 <= operand1 {
                         None
@@ -32,18 +33,36 @@ fn try_apply_legal(mut stack: Vec<u32>, token: Token) -> Option<Vec<u32>> {
                         Some(stack)
                     }
                 }
-                Op::Mul => {
-                    stack.push(operand0 * operand1);
-                    Some(stack)
-                }
+                Op::Mul => match operand0.checked_mul(operand1) {
+                    Some(result) => {
+                        stack.push(result);
+                        Some(stack)
+                    }
+                    None => None,
+                },
                 Op::Div => {
-                    if operand1 == 0 || operand0 % operand1 != 0 {
+                    if operand1 == 0 || !operand0.is_multiple_of(operand1) {
                         None
                     } else {
                         stack.push(operand0 / operand1);
                         Some(stack)
                     }
                 }
+                Op::Pow => match operand0.checked_pow(operand1) {
+                    Some(result) => {
+                        stack.push(result);
+                        Some(stack)
+                    }
+                    None => None,
+                },
+                Op::Mod => {
+                    if operand1 == 0 {
+                        None
+                    } else {
+                        stack.push(operand0 % operand1);
+                        Some(stack)
+                    }
+                }
             }
         }
     }
@@ -52,7 +71,7 @@ fn try_apply_legal(mut stack: Vec<u32>, token: Token) -> Option<Vec<u32>> {
 /// Try to apply a token on the postfix stack.
 /// This will either push a number or apply an operation.
 /// Returns the updated stack only when the operation is sensible.
-fn try_apply_sensible(mut stack: Vec<u32>, token: Token) -> Option<Vec<u32>> {
+pub(crate) fn try_apply_sensible(mut stack: Vec<u32>, token: Token) -> Option<Vec<u32>> {
     match token {
         Token::Num(n) => {
             // 0 shouldn't be in the list but just in case
@@ -73,8 +92,13 @@ fn try_apply_sensible(mut stack: Vec<u32>, token: Token) -> Option<Vec<u32>> {
                     if operand0 < operand1 {
                         None
                     } else {
-                        stack.push(operand0 + operand1);
-                        Some(stack)
+                        match operand0.checked_add(operand1) {
+                            Some(result) => {
+                                stack.push(result);
+                                Some(stack)
+                            }
+                            None => None,
+                        }
                     }
                 }
                 Op::Sub => {
@@ -92,19 +116,48 @@ fn try_apply_sensible(mut stack: Vec<u32>, token: Token) -> Option<Vec<u32>> {
                     if operand0 == 1 || operand1 == 1 || operand0 < operand1 {
                         None
                     } else {
-                        stack.push(operand0 * operand1);
-                        Some(stack)
+                        match operand0.checked_mul(operand1) {
+                            Some(result) => {
+                                stack.push(result);
+                                Some(stack)
+                            }
+                            None => None,
+                        }
                     }
                 }
                 Op::Div => {
                     // divide by 1 is not helpful
-                    if operand1 <= 1 || operand0 % operand1 != 0 {
+                    if operand1 <= 1 || !operand0.is_multiple_of(operand1) {
                         None
                     } else {
                         stack.push(operand0 / operand1);
                         Some(stack)
                     }
                 }
+                Op::Pow => {
+                    // raising to the power of 0 or 1, or raising 0 or 1, is not helpful
+                    if operand0 <= 1 || operand1 <= 1 {
+                        None
+                    } else {
+                        match operand0.checked_pow(operand1) {
+                            Some(result) => {
+                                stack.push(result);
+                                Some(stack)
+                            }
+                            None => None,
+                        }
+                    }
+                }
+                Op::Mod => {
+                    // mod by 0 or 1 is not helpful, and if the dividend is
+                    // smaller than the divisor the result is just the dividend
+                    if operand1 <= 1 || operand0 < operand1 {
+                        None
+                    } else {
+                        stack.push(operand0 % operand1);
+                        Some(stack)
+                    }
+                }
             }
         }
     }
@@ -113,13 +166,14 @@ fn try_apply_sensible(mut stack: Vec<u32>, token: Token) -> Option<Vec<u32>> {
 /// Find all solutions with the given parameters.
 ///
 /// Optionally filter out trivially-different solutions
-/// with the `dumb` flag.
+/// with the `dumb` flag. `ops` controls which operators are allowed.
 pub fn calc_postfix_sequences_all(
     numbers: &[u32],
     target: u32,
     dumb: bool,
+    ops: &[Op],
 ) -> HashSet<PostfixSequence> {
-    calc_postfix_sequences_all_recurse(numbers, target, dumb, vec![], vec![])
+    calc_postfix_sequences_all_recurse(numbers, target, dumb, ops, vec![], vec![])
 }
 
 /// Recursive implementation for `calc_postfix_sequences_all`.
@@ -127,6 +181,7 @@ fn calc_postfix_sequences_all_recurse(
     numbers: &[u32],
     target: u32,
     dumb: bool,
+    ops: &[Op],
     stack: Vec<u32>,
     history: PostfixSequence,
 ) -> HashSet<PostfixSequence> {
@@ -156,14 +211,22 @@ fn calc_postfix_sequences_all_recurse(
             let mut sub_history = history.clone();
             sub_history.push(num.into());
 
-            calc_postfix_sequences_all_recurse(&sub_numbers, target, dumb, sub_stack, sub_history)
+            calc_postfix_sequences_all_recurse(
+                &sub_numbers,
+                target,
+                dumb,
+                ops,
+                sub_stack,
+                sub_history,
+            )
         })
         .collect();
 
     // for each operation, try to apply and recurse
     // collect all solutions found via recursion
-    let operation_step_outputs = Op::iter()
-        .filter_map(|op| {
+    let operation_step_outputs = ops
+        .iter()
+        .filter_map(|&op| {
             if dumb {
                 try_apply_legal(stack.clone(), op.into())
             } else {
@@ -175,7 +238,7 @@ fn calc_postfix_sequences_all_recurse(
             let mut sub_history = history.clone();
             sub_history.push(op.into());
 
-            calc_postfix_sequences_all_recurse(numbers, target, dumb, sub_stack, sub_history)
+            calc_postfix_sequences_all_recurse(numbers, target, dumb, ops, sub_stack, sub_history)
         })
         .collect();
 
@@ -186,32 +249,66 @@ fn calc_postfix_sequences_all_recurse(
         .unwrap() // None only when iterator is empty
 }
 
+/// A search state proven unable to reach the target, keyed by the
+/// multiset of remaining numbers (sorted, since order doesn't matter)
+/// and the stack contents (kept in order, since `Sub`/`Div`/`Pow`/`Mod`
+/// are non-commutative).
+type DeadState = (Vec<u32>, Vec<u32>);
+
+/// Normalize a `(numbers, stack)` pair into a [`DeadState`] key.
+fn dead_state(numbers: &[u32], stack: &[u32]) -> DeadState {
+    let mut sorted_numbers = numbers.to_vec();
+    sorted_numbers.sort_unstable();
+    (sorted_numbers, stack.to_vec())
+}
+
 /// Find a solution with the given parameters, short circuiting
 /// as soon as the first solution is found.
 ///
 /// Optionally filter out trivially-different solutions
-/// with the `dumb` flag.
+/// with the `dumb` flag. `ops` controls which operators are allowed.
 pub fn calc_postfix_sequences_first(
     numbers: &[u32],
     target: u32,
     dumb: bool,
+    ops: &[Op],
 ) -> Option<PostfixSequence> {
-    calc_postfix_sequences_first_recurse(numbers, target, dumb, vec![], vec![])
+    let mut dead_states = HashSet::new();
+    calc_postfix_sequences_first_recurse(
+        numbers,
+        target,
+        dumb,
+        ops,
+        vec![],
+        vec![],
+        &mut dead_states,
+    )
 }
 
 /// Recursive implementation for `calc_postfix_sequences_first`.
+///
+/// `dead_states` memoizes `(remaining numbers, stack)` states already
+/// proven unable to reach the target, so they aren't re-explored via a
+/// different token ordering.
 fn calc_postfix_sequences_first_recurse(
     numbers: &[u32],
     target: u32,
     dumb: bool,
+    ops: &[Op],
     stack: Vec<u32>,
     history: PostfixSequence,
+    dead_states: &mut HashSet<DeadState>,
 ) -> Option<PostfixSequence> {
     // if target reached, return current history
     if stack.len() == 1 && stack[0] == target {
         return Some(history);
     }
 
+    let state = dead_state(numbers, &stack);
+    if dead_states.contains(&state) {
+        return None;
+    }
+
     // for each available number, try to apply then recurse
     // return as soon as the first solution is found
     let number_step_solution = numbers
@@ -232,7 +329,15 @@ fn calc_postfix_sequences_first_recurse(
             let mut sub_history = history.clone();
             sub_history.push(num.into());
 
-            calc_postfix_sequences_first_recurse(&sub_numbers, target, dumb, sub_stack, sub_history)
+            calc_postfix_sequences_first_recurse(
+                &sub_numbers,
+                target,
+                dumb,
+                ops,
+                sub_stack,
+                sub_history,
+                dead_states,
+            )
         });
     // short circuit if a solution is found, fall through otherwise
     if number_step_solution.is_some() {
@@ -241,8 +346,9 @@ fn calc_postfix_sequences_first_recurse(
 
     // for each operation, try to apply and recurse
     // return as soon as the first solution is found
-    let operation_step_solution = Op::iter()
-        .filter_map(|op| {
+    let operation_step_solution = ops
+        .iter()
+        .filter_map(|&op| {
             if dumb {
                 try_apply_legal(stack.clone(), op.into())
             } else {
@@ -254,11 +360,95 @@ fn calc_postfix_sequences_first_recurse(
             let mut sub_history = history.clone();
             sub_history.push(op.into());
 
-            calc_postfix_sequences_first_recurse(numbers, target, dumb, sub_stack, sub_history)
+            calc_postfix_sequences_first_recurse(
+                numbers, target, dumb, ops, sub_stack, sub_history, dead_states,
+            )
         });
-    // return regardless of solution found or not
-    // since there is nothing to fall through into
+
+    // this state cannot reach the target via any token ordering;
+    // remember it so other branches don't re-explore it
+    if operation_step_solution.is_none() {
+        dead_states.insert(state);
+    }
+
     operation_step_solution
 }
 
-// TODO: Associative filter
+/// The pre-memoization implementation of [`calc_postfix_sequences_first`],
+/// re-exploring every dead state it encounters via every token ordering
+/// that reaches it.
+///
+/// Kept only as a baseline for `benches/dead_state_memo.rs` to measure
+/// the dead-state cache against; the CLI and [`crate::solve_first`] both
+/// use the memoized version above.
+pub fn calc_postfix_sequences_first_unmemoized(
+    numbers: &[u32],
+    target: u32,
+    dumb: bool,
+    ops: &[Op],
+) -> Option<PostfixSequence> {
+    calc_postfix_sequences_first_unmemoized_recurse(numbers, target, dumb, ops, vec![], vec![])
+}
+
+/// Recursive implementation for [`calc_postfix_sequences_first_unmemoized`].
+fn calc_postfix_sequences_first_unmemoized_recurse(
+    numbers: &[u32],
+    target: u32,
+    dumb: bool,
+    ops: &[Op],
+    stack: Vec<u32>,
+    history: PostfixSequence,
+) -> Option<PostfixSequence> {
+    if stack.len() == 1 && stack[0] == target {
+        return Some(history);
+    }
+
+    let number_step_solution = numbers
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, &num)| {
+            if dumb {
+                try_apply_legal(stack.clone(), num.into())
+            } else {
+                try_apply_sensible(stack.clone(), num.into())
+            }
+            .map(|sub_stack| (idx, num, sub_stack))
+        })
+        .find_map(|(idx, num, sub_stack)| {
+            let mut sub_numbers = numbers.to_vec();
+            sub_numbers.swap_remove(idx);
+
+            let mut sub_history = history.clone();
+            sub_history.push(num.into());
+
+            calc_postfix_sequences_first_unmemoized_recurse(
+                &sub_numbers,
+                target,
+                dumb,
+                ops,
+                sub_stack,
+                sub_history,
+            )
+        });
+    if number_step_solution.is_some() {
+        return number_step_solution;
+    }
+
+    ops.iter()
+        .filter_map(|&op| {
+            if dumb {
+                try_apply_legal(stack.clone(), op.into())
+            } else {
+                try_apply_sensible(stack.clone(), op.into())
+            }
+            .map(|sub_stack| (op, sub_stack))
+        })
+        .find_map(|(op, sub_stack)| {
+            let mut sub_history = history.clone();
+            sub_history.push(op.into());
+
+            calc_postfix_sequences_first_unmemoized_recurse(
+                numbers, target, dumb, ops, sub_stack, sub_history,
+            )
+        })
+}
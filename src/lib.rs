@@ -0,0 +1,105 @@
+//! Library entry points for the Countdown numbers solver.
+//!
+//! Beyond the CLI binary, [`solve_all`] and [`solve_first`] expose the
+//! solver as a plain Rust API with structured errors instead of panics,
+//! so it can be embedded in other programs without shelling out to the CLI.
+
+pub mod compute;
+pub mod reachable;
+pub mod util;
+pub mod verify;
+
+use std::fmt;
+
+use util::{ExpBTree, InvalidPostfixSequenceError, Op};
+
+/// An error returned by the solver's public entry points.
+///
+/// Note there is no "division by zero" or "overflow" variant: `compute`'s
+/// search silently prunes those intermediate steps (see
+/// `try_apply_legal`/`try_apply_sensible`) rather than ever surfacing them
+/// here, so a variant for them would never be constructed.
+#[derive(Debug)]
+pub enum SolverError {
+    /// No numbers were given to work with.
+    EmptyNumberList,
+    /// A computed postfix sequence did not form a valid expression.
+    InvalidPostfixSequence(InvalidPostfixSequenceError),
+}
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::EmptyNumberList => write!(f, "no numbers were given"),
+            SolverError::InvalidPostfixSequence(err) => write!(f, "invalid expression: {}", err),
+        }
+    }
+}
+impl std::error::Error for SolverError {}
+
+/// The output representation of a solution, used by [`Options::format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Infix,
+    Postfix,
+}
+
+/// Options controlling how the solver searches for and formats solutions.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Include trivially-different solutions (e.g. `*1`, `/1`, `a+b` vs. `b+a`).
+    pub dumb: bool,
+    /// Which operators are allowed in generated expressions.
+    pub ops: Vec<Op>,
+    /// How a solution should be rendered via [`Options::format`].
+    pub output: OutputFormat,
+}
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            dumb: false,
+            ops: vec![Op::Add, Op::Sub, Op::Mul, Op::Div],
+            output: OutputFormat::Infix,
+        }
+    }
+}
+impl Options {
+    /// Render `tree` according to [`Options::output`].
+    pub fn format(&self, tree: &ExpBTree) -> String {
+        match self.output {
+            OutputFormat::Infix => tree.to_infix_string(),
+            OutputFormat::Postfix => tree.to_postfix_string(),
+        }
+    }
+}
+
+/// Find every solution for `numbers` and `target` under `options`.
+pub fn solve_all(
+    numbers: &[u32],
+    target: u32,
+    options: &Options,
+) -> Result<Vec<ExpBTree>, SolverError> {
+    if numbers.is_empty() {
+        return Err(SolverError::EmptyNumberList);
+    }
+
+    compute::calc_postfix_sequences_all(numbers, target, options.dumb, &options.ops)
+        .into_iter()
+        .map(|seq| ExpBTree::try_from(seq).map_err(SolverError::InvalidPostfixSequence))
+        .collect()
+}
+
+/// Find a solution for `numbers` and `target` under `options`, short
+/// circuiting as soon as the first one is found.
+pub fn solve_first(
+    numbers: &[u32],
+    target: u32,
+    options: &Options,
+) -> Result<Option<ExpBTree>, SolverError> {
+    if numbers.is_empty() {
+        return Err(SolverError::EmptyNumberList);
+    }
+
+    compute::calc_postfix_sequences_first(numbers, target, options.dumb, &options.ops)
+        .map(|seq| ExpBTree::try_from(seq).map_err(SolverError::InvalidPostfixSequence))
+        .transpose()
+}
@@ -1,13 +1,11 @@
-mod compute;
-mod post_processing;
-mod util;
-
 use clap::Parser;
 use itertools::Itertools;
 
-use crate::{
-    compute::{calc_postfix_sequences_all, calc_postfix_sequences_first},
-    util::ExpBTree,
+use countdown_numbers_solver::{
+    solve_all, solve_first,
+    util::Op,
+    verify::check_solution,
+    Options, OutputFormat,
 };
 
 #[derive(Debug, Parser)]
@@ -25,6 +23,19 @@ struct CliArgs {
     #[clap(short = 'p', long = "postfix")]
     postfix: bool,
 
+    /// Instead of searching for a solution, check whether the given infix
+    /// expression (e.g. "100*(3+4)-25") is a valid solution
+    #[clap(short = 'c', long = "check")]
+    check: Option<String>,
+
+    /// Also allow exponentiation (^) as an operator
+    #[clap(short = 'e', long = "pow")]
+    pow: bool,
+
+    /// Also allow modulo (%) as an operator
+    #[clap(short = 'o', long = "modulo")]
+    modulo: bool,
+
     /// The list of numbers to work with, delimited by commas
     #[clap(required = true, value_delimiter = ',', multiple_occurrences = false)]
     numbers: Vec<u32>,
@@ -38,6 +49,9 @@ fn main() {
         find_all,
         dumb,
         postfix,
+        check,
+        pow,
+        modulo,
         numbers,
         target,
     } = CliArgs::parse();
@@ -47,40 +61,50 @@ fn main() {
         find_all, dumb, postfix
     );
 
-    if find_all {
-        let solutions = calc_postfix_sequences_all(&numbers, target, dumb);
-        match solutions.len() {
-            0 => println!("No solution found"),
-            n => {
-                println!("{} solutions found", n);
-                solutions
-                    .into_iter()
-                    .map(|seq| ExpBTree::try_from(seq).unwrap()) // calculated sequence is always valid
-                    .dedup_by(|t0, t1| t0.commutative_eq(t1))
-                    .map(|tree| {
-                        if postfix {
-                            tree.to_postfix_string()
-                        } else {
-                            tree.to_infix_string()
-                        }
-                    })
-                    .sorted() // stable order
-                    .for_each(|repr| println!(" - {}", repr));
-            }
-        };
+    let mut ops = vec![Op::Add, Op::Sub, Op::Mul, Op::Div];
+    if pow {
+        ops.push(Op::Pow);
+    }
+    if modulo {
+        ops.push(Op::Mod);
+    }
+    let options = Options {
+        dumb,
+        ops,
+        output: if postfix {
+            OutputFormat::Postfix
+        } else {
+            OutputFormat::Infix
+        },
+    };
+
+    if let Some(expr) = check {
+        match check_solution(&expr, &numbers, target, &options.ops) {
+            Ok(true) => println!("\"{}\" is a valid solution", expr),
+            Ok(false) => println!("\"{}\" is not a valid solution", expr),
+            Err(err) => println!("\"{}\" could not be checked: {}", expr, err),
+        }
+    } else if find_all {
+        match solve_all(&numbers, target, &options) {
+            Ok(solutions) => match solutions.len() {
+                0 => println!("No solution found"),
+                n => {
+                    println!("{} solutions found", n);
+                    solutions
+                        .into_iter()
+                        .unique_by(|tree| tree.normalize()) // collapse associatively-equivalent solutions
+                        .map(|tree| options.format(&tree))
+                        .sorted() // stable order
+                        .for_each(|repr| println!(" - {}", repr));
+                }
+            },
+            Err(err) => println!("Error: {}", err),
+        }
     } else {
-        let solution = calc_postfix_sequences_first(&numbers, target, dumb);
-        match solution {
-            Some(seq) => {
-                let tree = ExpBTree::try_from(seq).unwrap(); // calculated sequence is always valid
-                let repr = if postfix {
-                    tree.to_postfix_string()
-                } else {
-                    tree.to_infix_string()
-                };
-                println!("Solution found: {}", repr);
-            }
-            None => println!("No solution found"),
-        };
+        match solve_first(&numbers, target, &options) {
+            Ok(Some(tree)) => println!("Solution found: {}", options.format(&tree)),
+            Ok(None) => println!("No solution found"),
+            Err(err) => println!("Error: {}", err),
+        }
     }
 }
@@ -0,0 +1,157 @@
+//! This module precomputes every value reachable from a fixed set of
+//! numbers, so repeated target queries become O(1) lookups instead of a
+//! fresh recursive search each time.
+
+use std::collections::HashMap;
+
+use crate::{
+    compute::try_apply_sensible,
+    util::{ExpBTree, Op, Token},
+};
+
+/// Merge every combination of a value from `lhs_map` with a value from
+/// `rhs_map` (in that order) that `try_apply_sensible` accepts into `out`,
+/// keyed by the resulting value.
+///
+/// Whichever combination is found first for a given value wins; later
+/// combinations producing the same value (whether equivalent or not) are
+/// discarded, since only one canonical expression per value is needed.
+fn combine_subsets(
+    lhs_map: &HashMap<u32, ExpBTree>,
+    rhs_map: &HashMap<u32, ExpBTree>,
+    ops: &[Op],
+    out: &mut HashMap<u32, ExpBTree>,
+) {
+    for (&lhs_val, lhs_expr) in lhs_map {
+        for (&rhs_val, rhs_expr) in rhs_map {
+            for &op in ops {
+                let stack = vec![lhs_val, rhs_val];
+                let result_stack = match try_apply_sensible(stack, Token::Op(op)) {
+                    Some(result_stack) => result_stack,
+                    None => continue,
+                };
+                let value = result_stack[0];
+                out.entry(value).or_insert_with(|| ExpBTree::Exp {
+                    lhs: Box::new(lhs_expr.clone()),
+                    rhs: Box::new(rhs_expr.clone()),
+                    op,
+                });
+            }
+        }
+    }
+}
+
+/// Every value reachable from a fixed set of numbers, each mapped to one
+/// canonical [`ExpBTree`] that produces it.
+///
+/// Built once via [`ReachableMap::build`] and then queried as many times
+/// as needed, instead of re-running the recursive search per target.
+pub struct ReachableMap {
+    values: HashMap<u32, ExpBTree>,
+}
+impl ReachableMap {
+    /// Enumerate every value reachable by combining any nonempty subset
+    /// of `numbers` using the operators in `ops`, taking only the steps
+    /// that `try_apply_sensible` accepts (so e.g. `x%1` is not taken).
+    pub fn build(numbers: &[u32], ops: &[Op]) -> Self {
+        let n = numbers.len();
+        assert!(
+            n <= usize::BITS as usize,
+            "too many numbers to enumerate subsets of"
+        );
+        let full_mask = if n == 0 { 0 } else { (1usize << n) - 1 };
+
+        // subset_maps[mask] holds the canonical reachable-value map for
+        // the subset of `numbers` denoted by `mask`
+        let mut subset_maps: Vec<HashMap<u32, ExpBTree>> = vec![HashMap::new(); full_mask + 1];
+        let mut values: HashMap<u32, ExpBTree> = HashMap::new();
+
+        for mask in 1..=full_mask {
+            let mut mask_map = HashMap::new();
+            if mask.count_ones() == 1 {
+                let idx = mask.trailing_zeros() as usize;
+                let num = numbers[idx];
+                mask_map.insert(num, ExpBTree::from(num));
+            } else {
+                // enumerate every way to split `mask` into two disjoint,
+                // nonempty submasks and combine their reachable values
+                let mut sub = (mask - 1) & mask;
+                while sub > 0 {
+                    let other = mask ^ sub;
+                    combine_subsets(&subset_maps[sub], &subset_maps[other], ops, &mut mask_map);
+                    sub = (sub - 1) & mask;
+                }
+            }
+
+            // first mask (in subset-enumeration order) to reach a value wins
+            for (&value, expr) in mask_map.iter() {
+                values.entry(value).or_insert_with(|| expr.clone());
+            }
+            subset_maps[mask] = mask_map;
+        }
+
+        ReachableMap { values }
+    }
+
+    /// Look up the canonical expression reaching `target`, if any.
+    pub fn query(&self, target: u32) -> Option<&ExpBTree> {
+        self.values.get(&target)
+    }
+
+    /// Iterate over every value reachable from the numbers this map was
+    /// built with.
+    pub fn reachable_targets(&self) -> impl Iterator<Item = u32> + '_ {
+        self.values.keys().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STANDARD_OPS: [Op; 4] = [Op::Add, Op::Sub, Op::Mul, Op::Div];
+
+    fn eval(expr: &ExpBTree) -> u32 {
+        match expr {
+            ExpBTree::Num(n) => *n,
+            ExpBTree::Exp { lhs, rhs, op } => {
+                let (l, r) = (eval(lhs), eval(rhs));
+                match op {
+                    Op::Add => l + r,
+                    Op::Sub => l - r,
+                    Op::Mul => l * r,
+                    Op::Div => l / r,
+                    Op::Pow => l.pow(r),
+                    Op::Mod => l % r,
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn query_finds_a_reachable_value_and_rejects_an_unreachable_one() {
+        let map = ReachableMap::build(&[1, 2, 3], &STANDARD_OPS);
+        assert!(map.query(6).is_some()); // 1+2+3
+        assert!(map.query(100).is_none());
+    }
+
+    #[test]
+    fn query_result_actually_evaluates_to_the_target() {
+        let map = ReachableMap::build(&[2, 3, 4], &STANDARD_OPS);
+        for target in map.reachable_targets() {
+            assert_eq!(eval(map.query(target).unwrap()), target);
+        }
+    }
+
+    /// Regression test: this set of numbers with every operator enabled
+    /// exhaustively explores pairwise combinations (unlike the recursive
+    /// search, which stops as soon as it reaches a target), so it used to
+    /// panic on a plain `u32` multiplication overflow well before any
+    /// single search path would (see `compute::try_apply_sensible`).
+    #[test]
+    fn build_does_not_panic_on_overflow_prone_operator_sets() {
+        let all_ops = [Op::Add, Op::Sub, Op::Mul, Op::Div, Op::Pow, Op::Mod];
+        let map = ReachableMap::build(&[2, 3, 4, 5, 7], &all_ops);
+        assert!(!map.reachable_targets().collect::<Vec<_>>().is_empty());
+    }
+}
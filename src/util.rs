@@ -12,6 +12,10 @@ pub enum Op {
     Sub,
     Mul,
     Div,
+    /// Exponentiation, e.g. `2^3`. Right-associative.
+    Pow,
+    /// Modulo, e.g. `7%3`.
+    Mod,
 }
 impl fmt::Display for Op {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -20,11 +24,51 @@ impl fmt::Display for Op {
             Op::Sub => "-",
             Op::Mul => "*",
             Op::Div => "/",
+            Op::Pow => "^",
+            Op::Mod => "%",
         };
         write!(f, "{}", repr)
     }
 }
 
+/// The associativity of an operation, i.e. how a chain of the same
+/// operation at equal precedence should be grouped when no parentheses
+/// are present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    /// `a op b op c` groups as `(a op b) op c`.
+    Left,
+    /// `a op b op c` groups as `a op (b op c)`.
+    Right,
+}
+impl Op {
+    /// The associativity of this operation, used to decide when a
+    /// sub-expression of equal precedence needs parenthesizing.
+    pub fn associativity(&self) -> Associativity {
+        match self {
+            Op::Pow => Associativity::Right,
+            Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Mod => Associativity::Left,
+        }
+    }
+    /// A stable per-variant rank, used only to give every operator a
+    /// distinct position in [`ExpBTree`]'s canonical ordering.
+    ///
+    /// This is deliberately separate from [`Op`]'s precedence-based
+    /// [`Ord`] impl, under which e.g. `Mul`/`Div`/`Mod` all compare equal
+    /// — fine for the shunting-yard parser, but useless as a tie-breaker
+    /// for distinguishing sibling operands built with different operators.
+    fn rank(&self) -> u8 {
+        match self {
+            Op::Add => 0,
+            Op::Sub => 1,
+            Op::Mul => 2,
+            Op::Div => 3,
+            Op::Pow => 4,
+            Op::Mod => 5,
+        }
+    }
+}
+
 /// An atomic unit in a postfix-order expression.
 ///
 /// Either a positive integer or an operation.
@@ -58,14 +102,18 @@ impl PartialOrd for Op {
     }
 }
 /// Operational precedence rules.
+///
+/// From lowest to highest: `Add`/`Sub`, then `Mul`/`Div`/`Mod`, then `Pow`.
 impl Ord for Op {
     fn cmp(&self, other: &Self) -> Ordering {
         use Op::*;
         use Ordering::*;
         match (self, other) {
-            (Add | Sub, Add | Sub) | (Mul | Div, Mul | Div) => Equal,
-            (Add | Sub, Mul | Div) => Less,
-            (Mul | Div, Add | Sub) => Greater,
+            (Add | Sub, Add | Sub) | (Mul | Div | Mod, Mul | Div | Mod) | (Pow, Pow) => Equal,
+            (Add | Sub, Mul | Div | Mod | Pow) => Less,
+            (Mul | Div | Mod, Add | Sub) => Greater,
+            (Mul | Div | Mod, Pow) => Less,
+            (Pow, Add | Sub | Mul | Div | Mod) => Greater,
         }
     }
 }
@@ -88,7 +136,7 @@ impl fmt::Display for InvalidPostfixSequenceError {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ExpBTree {
     Num(u32),
     Exp {
@@ -108,10 +156,10 @@ impl TryFrom<PostfixSequence> for ExpBTree {
     fn try_from(seq: PostfixSequence) -> Result<Self, Self::Error> {
         fn try_from_impl(seq: PostfixSequence) -> Option<ExpBTree> {
             let mut stack = vec![];
-            for token in seq.iter() {
+            for &token in seq.iter() {
                 match token {
-                    &Token::Num(n) => stack.push(n.into()),
-                    &Token::Op(op) => {
+                    Token::Num(n) => stack.push(n.into()),
+                    Token::Op(op) => {
                         let rhs = Box::new(stack.pop()?);
                         let lhs = Box::new(stack.pop()?);
                         let exp = ExpBTree::Exp { lhs, rhs, op };
@@ -130,7 +178,44 @@ impl TryFrom<PostfixSequence> for ExpBTree {
         }
 
         let seq_repr = to_postfix_string(&seq);
-        try_from_impl(seq).ok_or_else(|| InvalidPostfixSequenceError { seq_repr })
+        try_from_impl(seq).ok_or(InvalidPostfixSequenceError { seq_repr })
+    }
+}
+impl PartialOrd for ExpBTree {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+/// A canonical total order over expressions, used by [`ExpBTree::normalize`]
+/// to sort associative operand bags into a deterministic order.
+///
+/// Note this does *not* reuse [`Op`]'s precedence-based `Ord`: that impl
+/// considers e.g. `Mul`/`Div`/`Mod` equal to each other, which would make
+/// this comparison fall back to input order for sibling operands built
+/// with different (but equal-precedence) operators, defeating dedup.
+impl Ord for ExpBTree {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (ExpBTree::Num(s), ExpBTree::Num(o)) => s.cmp(o),
+            (ExpBTree::Num(_), ExpBTree::Exp { .. }) => Ordering::Less,
+            (ExpBTree::Exp { .. }, ExpBTree::Num(_)) => Ordering::Greater,
+            (
+                ExpBTree::Exp {
+                    lhs: s_lhs,
+                    rhs: s_rhs,
+                    op: s_op,
+                },
+                ExpBTree::Exp {
+                    lhs: o_lhs,
+                    rhs: o_rhs,
+                    op: o_op,
+                },
+            ) => s_op
+                .rank()
+                .cmp(&o_op.rank())
+                .then_with(|| s_lhs.cmp(o_lhs))
+                .then_with(|| s_rhs.cmp(o_rhs)),
+        }
     }
 }
 impl ExpBTree {
@@ -157,12 +242,54 @@ impl ExpBTree {
                         s_lhs.commutative_eq(o_lhs) && s_rhs.commutative_eq(o_rhs)
                             || s_lhs.commutative_eq(o_rhs) && s_rhs.commutative_eq(o_lhs)
                     }
-                    Op::Sub | Op::Div => s_lhs.commutative_eq(o_lhs) && s_rhs.commutative_eq(o_rhs),
+                    Op::Sub | Op::Div | Op::Pow | Op::Mod => {
+                        s_lhs.commutative_eq(o_lhs) && s_rhs.commutative_eq(o_rhs)
+                    }
                 }
             }
             _ => false,
         }
     }
+    /// Collapse associatively-equivalent expressions (e.g. `(2+3)+4` and
+    /// `2+(3+4)`) onto a single canonical tree.
+    ///
+    /// Maximal chains of the same associative operator (`+`/`*`) are
+    /// flattened into a bag of operands, each recursively normalized,
+    /// sorted into a canonical order, then rebuilt into a left-leaning
+    /// tree. `-`/`/`/`^`/`%` are not associative and are left as-is
+    /// (aside from normalizing their operands).
+    pub fn normalize(&self) -> ExpBTree {
+        match self {
+            ExpBTree::Num(n) => ExpBTree::Num(*n),
+            ExpBTree::Exp { op, .. } if matches!(op, Op::Add | Op::Mul) => {
+                let op = *op;
+                let mut operands = vec![];
+                self.flatten_chain(op, &mut operands);
+
+                let mut operands: Vec<ExpBTree> =
+                    operands.into_iter().map(|operand| operand.normalize()).collect();
+                operands.sort();
+
+                rebuild_left_leaning(op, operands)
+            }
+            ExpBTree::Exp { lhs, rhs, op } => ExpBTree::Exp {
+                lhs: Box::new(lhs.normalize()),
+                rhs: Box::new(rhs.normalize()),
+                op: *op,
+            },
+        }
+    }
+    /// Push every leaf of the maximal chain of `chain_op` rooted at
+    /// `self` into `out`, in no particular order.
+    fn flatten_chain(&self, chain_op: Op, out: &mut Vec<ExpBTree>) {
+        match self {
+            ExpBTree::Exp { lhs, rhs, op } if *op == chain_op => {
+                lhs.flatten_chain(chain_op, out);
+                rhs.flatten_chain(chain_op, out);
+            }
+            other => out.push(other.clone()),
+        }
+    }
     pub fn to_postfix_string(&self) -> String {
         match self {
             ExpBTree::Num(n) => n.to_string(),
@@ -181,20 +308,30 @@ impl ExpBTree {
         match self {
             &ExpBTree::Num(n) => (n.to_string(), None),
             ExpBTree::Exp { lhs, rhs, op } => {
+                // on equal precedence, the side that's "against the grain" of
+                // associativity needs parentheses to preserve grouping
                 let (lhs_repr_raw, lhs_op) = lhs.to_infix_string_impl();
                 let lhs_repr = match lhs_op {
                     None => lhs_repr_raw,
                     Some(lhs_op) => match lhs_op.cmp(op) {
                         Ordering::Less => format!("({})", lhs_repr_raw),
-                        Ordering::Equal | Ordering::Greater => lhs_repr_raw,
+                        Ordering::Greater => lhs_repr_raw,
+                        Ordering::Equal => match op.associativity() {
+                            Associativity::Left => lhs_repr_raw,
+                            Associativity::Right => format!("({})", lhs_repr_raw),
+                        },
                     },
                 };
                 let (rhs_repr_raw, rhs_op) = rhs.to_infix_string_impl();
                 let rhs_repr = match rhs_op {
                     None => rhs_repr_raw,
                     Some(rhs_op) => match rhs_op.cmp(op) {
-                        Ordering::Less | Ordering::Equal => format!("({})", rhs_repr_raw),
+                        Ordering::Less => format!("({})", rhs_repr_raw),
                         Ordering::Greater => rhs_repr_raw,
+                        Ordering::Equal => match op.associativity() {
+                            Associativity::Left => format!("({})", rhs_repr_raw),
+                            Associativity::Right => rhs_repr_raw,
+                        },
                     },
                 };
                 let repr = format!("{}{}{}", lhs_repr, op, rhs_repr);
@@ -203,3 +340,57 @@ impl ExpBTree {
         }
     }
 }
+
+/// Rebuild a left-leaning binary tree applying `op` across `operands`
+/// in order, e.g. `[a, b, c]` becomes `(a op b) op c`.
+fn rebuild_left_leaning(op: Op, mut operands: Vec<ExpBTree>) -> ExpBTree {
+    let first = operands.remove(0);
+    operands.into_iter().fold(first, |acc, operand| ExpBTree::Exp {
+        lhs: Box::new(acc),
+        rhs: Box::new(operand),
+        op,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `normalize()` must dedup associatively-equivalent trees even when
+    /// the commuted operands are themselves `Exp`s built with different
+    /// (but equal-precedence) operators, e.g. a `Mul` and a `Mod` subtree.
+    #[test]
+    fn normalize_dedups_across_distinct_sibling_operators() {
+        let mul = ExpBTree::Exp {
+            lhs: Box::new(4.into()),
+            rhs: Box::new(6.into()),
+            op: Op::Mul,
+        };
+        let modulo = ExpBTree::Exp {
+            lhs: Box::new(4.into()),
+            rhs: Box::new(6.into()),
+            op: Op::Mod,
+        };
+
+        let tree1 = ExpBTree::Exp {
+            lhs: Box::new(ExpBTree::Exp {
+                lhs: Box::new(mul.clone()),
+                rhs: Box::new(modulo.clone()),
+                op: Op::Add,
+            }),
+            rhs: Box::new(100.into()),
+            op: Op::Add,
+        };
+        let tree2 = ExpBTree::Exp {
+            lhs: Box::new(ExpBTree::Exp {
+                lhs: Box::new(modulo),
+                rhs: Box::new(mul),
+                op: Op::Add,
+            }),
+            rhs: Box::new(100.into()),
+            op: Op::Add,
+        };
+
+        assert_eq!(tree1.normalize(), tree2.normalize());
+    }
+}
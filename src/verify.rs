@@ -0,0 +1,383 @@
+//! This module contains code for parsing a user-supplied infix expression
+//! and checking it against a set of numbers and a target, i.e. the reverse
+//! direction of [`crate::compute`].
+
+use std::fmt;
+
+use crate::{
+    compute::{try_apply_legal, PostfixSequence},
+    util::{Associativity, ExpBTree, InvalidPostfixSequenceError, Op, Token},
+};
+
+/// A single token produced while scanning the raw expression string,
+/// before it is rearranged into postfix order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InfixToken {
+    Num(u32),
+    Op(Op),
+    LParen,
+    RParen,
+}
+
+/// An error encountered while tokenizing or shunting an infix expression.
+#[derive(Debug)]
+pub enum ParseError {
+    /// An unrecognized character was found in the expression.
+    UnexpectedChar(char),
+    /// Parentheses in the expression are not balanced.
+    MismatchedParens,
+    /// The expression contained no tokens.
+    EmptyExpression,
+    /// A number literal in the expression overflowed a `u32`.
+    NumberOverflow,
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedChar(c) => write!(f, "unexpected character '{}'", c),
+            ParseError::MismatchedParens => write!(f, "mismatched parentheses"),
+            ParseError::EmptyExpression => write!(f, "expression is empty"),
+            ParseError::NumberOverflow => write!(f, "number literal is too large"),
+        }
+    }
+}
+
+/// An error encountered while checking a parsed expression against
+/// the numbers and target of a Countdown puzzle.
+#[derive(Debug)]
+pub enum CheckError {
+    /// The expression could not be tokenized or shunted into postfix order.
+    Parse(ParseError),
+    /// The resulting postfix sequence is not a well-formed expression
+    /// (e.g. it leaves more than one value on the stack).
+    InvalidExpression(InvalidPostfixSequenceError),
+    /// The expression uses numbers that are not available, or uses a
+    /// number more times than it is available.
+    NumberMismatch,
+    /// An intermediate step produced a negative, zero or fractional
+    /// difference or quotient, divided or took a modulo by zero, or
+    /// overflowed a `u32`.
+    IllegalStep,
+    /// The expression uses an operator that isn't in the allowed set.
+    DisallowedOperator(Op),
+}
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::Parse(err) => write!(f, "{}", err),
+            CheckError::InvalidExpression(err) => write!(f, "{}", err),
+            CheckError::NumberMismatch => {
+                write!(f, "expression uses numbers that are not available")
+            }
+            CheckError::IllegalStep => {
+                write!(
+                    f,
+                    "expression has a non-positive, fractional, division-by-zero, or overflowing step"
+                )
+            }
+            CheckError::DisallowedOperator(op) => {
+                write!(f, "operator '{}' is not enabled for this search", op)
+            }
+        }
+    }
+}
+
+/// Break a raw expression string into [`InfixToken`]s.
+fn tokenize(expr: &str) -> Result<Vec<InfixToken>, ParseError> {
+    let mut tokens = vec![];
+    let mut chars = expr.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' => {
+                chars.next();
+            }
+            '0'..='9' => {
+                let mut digits = String::new();
+                while let Some(&d) = chars.peek() {
+                    if d.is_ascii_digit() {
+                        digits.push(d);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let n = digits.parse().map_err(|_| ParseError::NumberOverflow)?;
+                tokens.push(InfixToken::Num(n));
+            }
+            '+' => {
+                tokens.push(InfixToken::Op(Op::Add));
+                chars.next();
+            }
+            '-' => {
+                tokens.push(InfixToken::Op(Op::Sub));
+                chars.next();
+            }
+            '*' => {
+                tokens.push(InfixToken::Op(Op::Mul));
+                chars.next();
+            }
+            '/' => {
+                tokens.push(InfixToken::Op(Op::Div));
+                chars.next();
+            }
+            '^' => {
+                tokens.push(InfixToken::Op(Op::Pow));
+                chars.next();
+            }
+            '%' => {
+                tokens.push(InfixToken::Op(Op::Mod));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(InfixToken::LParen);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(InfixToken::RParen);
+                chars.next();
+            }
+            c => return Err(ParseError::UnexpectedChar(c)),
+        }
+    }
+
+    if tokens.is_empty() {
+        Err(ParseError::EmptyExpression)
+    } else {
+        Ok(tokens)
+    }
+}
+
+/// Whether the operator on top of the shunting-yard stack should be
+/// popped before `next_op` is pushed.
+enum Precedence {
+    Pop,
+    Stop,
+}
+
+/// Decide whether `top_op` (already on the operator stack) should be
+/// popped before `next_op` (about to be pushed).
+///
+/// Mirrors [`Op`]'s precedence-based [`Ord`] impl, except at equal
+/// precedence: there, `next_op`'s associativity decides, so a
+/// right-associative chain like `2^3^2` groups as `2^(3^2)` rather than
+/// `(2^3)^2` (see [`crate::util::ExpBTree::to_infix_string`] for the
+/// matching logic on the output side).
+fn precedence_check(top_op: Op, next_op: Op) -> Precedence {
+    use std::cmp::Ordering::*;
+    match top_op.cmp(&next_op) {
+        Greater => Precedence::Pop,
+        Less => Precedence::Stop,
+        Equal => match next_op.associativity() {
+            Associativity::Left => Precedence::Pop,
+            Associativity::Right => Precedence::Stop,
+        },
+    }
+}
+
+/// An entry on the shunting-yard operator stack: either an operator or
+/// an open parenthesis waiting for its match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StackItem {
+    Op(Op),
+    LParen,
+}
+
+/// Parse a raw infix expression (e.g. `"100*(3+4)-25"`) into a
+/// [`PostfixSequence`] via the shunting-yard algorithm.
+pub fn parse_infix(expr: &str) -> Result<PostfixSequence, ParseError> {
+    let infix_tokens = tokenize(expr)?;
+
+    let mut output = vec![];
+    let mut op_stack: Vec<StackItem> = vec![];
+    for token in infix_tokens {
+        match token {
+            InfixToken::Num(n) => output.push(Token::Num(n)),
+            InfixToken::Op(next_op) => {
+                while let Some(&StackItem::Op(top_op)) = op_stack.last() {
+                    match precedence_check(top_op, next_op) {
+                        Precedence::Pop => {
+                            op_stack.pop();
+                            output.push(Token::Op(top_op));
+                        }
+                        Precedence::Stop => break,
+                    }
+                }
+                op_stack.push(StackItem::Op(next_op));
+            }
+            InfixToken::LParen => op_stack.push(StackItem::LParen),
+            InfixToken::RParen => loop {
+                match op_stack.pop() {
+                    Some(StackItem::Op(op)) => output.push(Token::Op(op)),
+                    Some(StackItem::LParen) => break,
+                    None => return Err(ParseError::MismatchedParens),
+                }
+            },
+        }
+    }
+
+    // pop any remaining operators; a leftover paren means it was never closed
+    while let Some(item) = op_stack.pop() {
+        match item {
+            StackItem::Op(op) => output.push(Token::Op(op)),
+            StackItem::LParen => return Err(ParseError::MismatchedParens),
+        }
+    }
+
+    Ok(output)
+}
+
+/// Check whether `numbers_used` (with repeats) is a sub-multiset of
+/// `available` (with repeats), i.e. every number is used at most as many
+/// times as it appears in `available`.
+fn is_submultiset(numbers_used: &[u32], available: &[u32]) -> bool {
+    let mut remaining = available.to_vec();
+    for &n in numbers_used {
+        match remaining.iter().position(|&m| m == n) {
+            Some(idx) => {
+                remaining.swap_remove(idx);
+            }
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Parse `expr` and check whether it is a valid Countdown solution for
+/// `numbers` and `target`: it only uses operators in `ops`, each number
+/// is used at most once, and every intermediate step is accepted by
+/// `try_apply_legal` (no negative, zero or fractional difference or
+/// quotient, no division or modulo by zero, no `u32` overflow).
+pub fn check_solution(
+    expr: &str,
+    numbers: &[u32],
+    target: u32,
+    ops: &[Op],
+) -> Result<bool, CheckError> {
+    let seq = parse_infix(expr).map_err(CheckError::Parse)?;
+
+    for token in &seq {
+        if let Token::Op(op) = token {
+            if !ops.contains(op) {
+                return Err(CheckError::DisallowedOperator(*op));
+            }
+        }
+    }
+
+    let numbers_used: Vec<u32> = seq
+        .iter()
+        .filter_map(|token| match token {
+            Token::Num(n) => Some(*n),
+            Token::Op(_) => None,
+        })
+        .collect();
+    if !is_submultiset(&numbers_used, numbers) {
+        return Err(CheckError::NumberMismatch);
+    }
+
+    // ensures the sequence is a well-formed expression (single resulting value)
+    ExpBTree::try_from(seq.clone()).map_err(CheckError::InvalidExpression)?;
+
+    let mut stack = vec![];
+    for token in seq {
+        stack = try_apply_legal(stack, token).ok_or(CheckError::IllegalStep)?;
+    }
+
+    Ok(stack.len() == 1 && stack[0] == target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STANDARD_OPS: [Op; 4] = [Op::Add, Op::Sub, Op::Mul, Op::Div];
+    const ALL_OPS: [Op; 6] = [Op::Add, Op::Sub, Op::Mul, Op::Div, Op::Pow, Op::Mod];
+
+    #[test]
+    fn parse_infix_respects_precedence() {
+        // without precedence this would shunt as "(10+2)*3", i.e. 10,2,+,3,*
+        let seq = parse_infix("10+2*3").unwrap();
+        assert_eq!(
+            seq,
+            vec![
+                Token::Num(10),
+                Token::Num(2),
+                Token::Num(3),
+                Token::Op(Op::Mul),
+                Token::Op(Op::Add),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_infix_respects_parens() {
+        let seq = parse_infix("(10+2)*3").unwrap();
+        assert_eq!(
+            seq,
+            vec![
+                Token::Num(10),
+                Token::Num(2),
+                Token::Op(Op::Add),
+                Token::Num(3),
+                Token::Op(Op::Mul),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_infix_pow_is_right_associative() {
+        // left-associative shunting would give "(2^2)^3" = 2,2,^,3,^
+        let seq = parse_infix("2^2^3").unwrap();
+        assert_eq!(
+            seq,
+            vec![
+                Token::Num(2),
+                Token::Num(2),
+                Token::Num(3),
+                Token::Op(Op::Pow),
+                Token::Op(Op::Pow),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_infix_rejects_mismatched_parens() {
+        assert!(matches!(parse_infix("(1+2"), Err(ParseError::MismatchedParens)));
+        assert!(matches!(parse_infix("1+2)"), Err(ParseError::MismatchedParens)));
+    }
+
+    #[test]
+    fn parse_infix_rejects_overflowing_number() {
+        assert!(matches!(
+            parse_infix("99999999999+1"),
+            Err(ParseError::NumberOverflow)
+        ));
+    }
+
+    #[test]
+    fn check_solution_accepts_a_valid_solution() {
+        assert!(matches!(
+            check_solution("10*2+3", &[1, 2, 3, 10], 23, &STANDARD_OPS),
+            Ok(true)
+        ));
+    }
+
+    #[test]
+    fn check_solution_rejects_numbers_not_available() {
+        assert!(matches!(
+            check_solution("10*2+5", &[1, 2, 3, 10], 23, &STANDARD_OPS),
+            Err(CheckError::NumberMismatch)
+        ));
+    }
+
+    #[test]
+    fn check_solution_rejects_disallowed_operators() {
+        assert!(matches!(
+            check_solution("2^3+4", &[2, 3, 4], 12, &STANDARD_OPS),
+            Err(CheckError::DisallowedOperator(Op::Pow))
+        ));
+        assert!(matches!(
+            check_solution("2^3+4", &[2, 3, 4], 12, &ALL_OPS),
+            Ok(true)
+        ));
+    }
+}